@@ -0,0 +1,344 @@
+//! A [LogWriter](crate::LogWriter) variant that keeps the blocking transport I/O off the
+//! logging caller's thread.
+use std::{
+    io::{self, ErrorKind},
+    thread::JoinHandle,
+};
+
+use arrayvec::ArrayVec;
+use crossbeam_channel::{Receiver, SendError, Sender, TrySendError};
+use flexi_logger::{DeferredNow, Record};
+use syslog_fmt::v5424;
+use syslog_net::Transport;
+
+use crate::{log_writer::FullBufferErrorStrategy, LevelToSeverity};
+
+/// A message sent from logging callers to the background [Transport] worker.
+///
+/// `Msg` carries the same `ArrayVec<u8, CAP>` the caller formatted into rather than copying it
+/// into a heap-allocated `Vec`, so handing a line off to the worker doesn't allocate.
+enum ChannelMsg<const CAP: usize> {
+    /// A fully formatted syslog line, ready to hand to the transport.
+    Msg(ArrayVec<u8, CAP>),
+    /// Asks the worker to flush the transport and signal completion on the given channel.
+    Flush(Sender<()>),
+    /// Asks the worker to stop draining the channel and exit.
+    Quit,
+}
+
+/// Like [LogWriter](crate::LogWriter), but formatting happens on the caller's thread while the
+/// blocking [Transport] I/O happens on a single dedicated worker thread, so `log::info!` and
+/// friends never block on socket writes.
+///
+/// Unlike [LogWriter](crate::LogWriter), records never pass through
+/// [StructuredDataConfig](crate::StructuredDataConfig) here — `log::kv` pairs are discarded
+/// rather than rendered into a STRUCTURED-DATA element. There's also no [FilterSpec](crate::FilterSpec)
+/// support: every record is gated solely by `max_log_level`, regardless of target. Nor is there a
+/// [FormatFn](crate::FormatFn) hook: the message body is always `record.args()` verbatim.
+pub struct AsyncLogWriter<const CAP: usize> {
+    formatter: v5424::Formatter,
+    sender: Sender<ChannelMsg<CAP>>,
+    worker: Option<JoinHandle<()>>,
+    max_log_level: log::LevelFilter,
+    level_to_severity: LevelToSeverity,
+    full_buffer_error_strategy: FullBufferErrorStrategy,
+}
+
+impl<const CAP: usize> AsyncLogWriter<CAP> {
+    /// Spawns the worker thread and returns a writer that feeds it over a channel bounded to
+    /// `capacity` messages.
+    pub fn new(
+        formatter: v5424::Formatter,
+        transport: Transport,
+        capacity: usize,
+        max_log_level: log::LevelFilter,
+        level_to_severity: LevelToSeverity,
+        full_buffer_error_strategy: FullBufferErrorStrategy,
+    ) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(capacity);
+        let worker = std::thread::Builder::new()
+            .name("flexi-syslog-async".into())
+            .spawn(move || worker_loop(transport, receiver))
+            .expect("failed to spawn flexi-syslog async worker thread");
+
+        Self {
+            formatter,
+            sender,
+            worker: Some(worker),
+            max_log_level,
+            level_to_severity,
+            full_buffer_error_strategy,
+        }
+    }
+
+    /// Pushes a formatted `buf` onto the worker's channel as a [ChannelMsg::Msg]. When the
+    /// channel is full, `full_buffer_error_strategy` decides whether to drop `buf`
+    /// ([FullBufferErrorStrategy::Ignore]) or block the caller until the worker drains space
+    /// ([FullBufferErrorStrategy::Fail]).
+    ///
+    /// Control messages ([ChannelMsg::Flush], [ChannelMsg::Quit]) never go through here: they're
+    /// sent directly via `self.sender.send`, bypassing `full_buffer_error_strategy`, since
+    /// dropping a flush/quit request under backpressure would silently break the caller's
+    /// expectations rather than just delaying a log line.
+    fn send(&self, buf: ArrayVec<u8, CAP>) -> io::Result<()> {
+        match self.full_buffer_error_strategy {
+            FullBufferErrorStrategy::Ignore => match self.sender.try_send(ChannelMsg::Msg(buf)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Ok(()),
+                Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                    ErrorKind::BrokenPipe,
+                    "flexi-syslog worker thread gone",
+                )),
+            },
+            FullBufferErrorStrategy::Fail => match self.sender.send(ChannelMsg::Msg(buf)) {
+                Ok(()) => Ok(()),
+                Err(SendError(_)) => Err(io::Error::new(
+                    ErrorKind::BrokenPipe,
+                    "flexi-syslog worker thread gone",
+                )),
+            },
+        }
+    }
+}
+
+fn worker_loop<const CAP: usize>(mut transport: Transport, receiver: Receiver<ChannelMsg<CAP>>) {
+    for msg in receiver.iter() {
+        match msg {
+            ChannelMsg::Msg(buf) => {
+                let _ = transport.send(&buf);
+            }
+            ChannelMsg::Flush(ack) => {
+                let _ = transport.flush();
+                let _ = ack.send(());
+            }
+            ChannelMsg::Quit => break,
+        }
+    }
+}
+
+impl<const CAP: usize> flexi_logger::writers::LogWriter for AsyncLogWriter<CAP> {
+    fn write(&self, _now: &mut DeferredNow, record: &Record<'_>) -> io::Result<()> {
+        if record.level() > self.max_log_level {
+            return Ok(());
+        }
+
+        let mut buf = ArrayVec::<u8, CAP>::new();
+        let severity = (self.level_to_severity)(record.level());
+
+        let res = self.formatter.format(&mut buf, severity, record.args(), None);
+
+        if let Err(e) = res {
+            if e.kind() != ErrorKind::WriteZero {
+                match self.full_buffer_error_strategy {
+                    FullBufferErrorStrategy::Ignore => (),
+                    FullBufferErrorStrategy::Fail => return Err(e),
+                }
+            }
+        }
+
+        self.send(buf)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let (ack_tx, ack_rx) = crossbeam_channel::bounded(0);
+        self.sender
+            .send(ChannelMsg::Flush(ack_tx))
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "flexi-syslog worker thread gone"))?;
+        ack_rx
+            .recv()
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "flexi-syslog worker thread gone"))
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
+
+impl<const CAP: usize> Drop for AsyncLogWriter<CAP> {
+    fn drop(&mut self) {
+        let _ = self.sender.send(ChannelMsg::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::UdpSocket, time::Duration, time::Instant};
+
+    use flexi_logger::{writers::LogWriter as _, DeferredNow};
+
+    use super::*;
+    use crate::{default_level_to_severity, Facility};
+
+    /// Binds a UDP socket to receive whatever the worker thread sends, alongside the address an
+    /// [AsyncLogWriter]'s [Transport] should target.
+    fn recv_socket() -> (UdpSocket, std::net::SocketAddr) {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("bind recv socket");
+        socket
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .expect("set read timeout");
+        let addr = socket.local_addr().expect("local addr");
+        (socket, addr)
+    }
+
+    /// Builds a fixed-capacity `ArrayVec` from `bytes`, for tests that need a [ChannelMsg::Msg]
+    /// without going through a real [AsyncLogWriter::write].
+    fn arrvec(bytes: &[u8]) -> ArrayVec<u8, 256> {
+        let mut buf = ArrayVec::new();
+        buf.try_extend_from_slice(bytes).expect("bytes fit in test capacity");
+        buf
+    }
+
+    fn test_writer(
+        addr: std::net::SocketAddr,
+        capacity: usize,
+        strategy: FullBufferErrorStrategy,
+    ) -> AsyncLogWriter<256> {
+        let formatter = v5424::Formatter::new(Facility::User.code(), None, "test".to_string(), 1);
+        let transport = Transport::udp(addr).expect("build udp transport");
+        AsyncLogWriter::new(
+            formatter,
+            transport,
+            capacity,
+            log::LevelFilter::Info,
+            default_level_to_severity,
+            strategy,
+        )
+    }
+
+    #[test]
+    fn worker_loop_drains_queued_messages_in_order_then_honors_quit() {
+        let (socket, addr) = recv_socket();
+        let transport = Transport::udp(addr).expect("build udp transport");
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+
+        sender.send(ChannelMsg::Msg(arrvec(b"first"))).expect("send first");
+        sender.send(ChannelMsg::Msg(arrvec(b"second"))).expect("send second");
+        sender.send(ChannelMsg::Quit).expect("send quit");
+        sender.send(ChannelMsg::Msg(arrvec(b"never"))).expect("send never");
+
+        worker_loop(transport, receiver);
+
+        let mut buf = [0u8; 64];
+        let (n, _) = socket.recv_from(&mut buf).expect("first datagram");
+        assert_eq!(&buf[..n], b"first");
+        let (n, _) = socket.recv_from(&mut buf).expect("second datagram");
+        assert_eq!(&buf[..n], b"second");
+        assert!(
+            socket.recv_from(&mut buf).is_err(),
+            "Quit must stop the worker before it reaches the message queued after it"
+        );
+    }
+
+    #[test]
+    fn flush_does_not_return_until_prior_messages_are_sent() {
+        let (socket, addr) = recv_socket();
+        let writer = test_writer(addr, 8, FullBufferErrorStrategy::Ignore);
+        let mut now = DeferredNow::new();
+
+        for i in 0..3 {
+            let record = log::Record::builder()
+                .level(log::Level::Info)
+                .target("test")
+                .args(format_args!("line{i}"))
+                .build();
+            writer.write(&mut now, &record).expect("write queues a message");
+        }
+        writer.flush().expect("flush blocks until the worker acks");
+
+        // The ack can only arrive after the worker has drained every Msg queued ahead of the
+        // Flush, so all three datagrams must already be sitting in the socket's receive buffer.
+        let mut buf = [0u8; 64];
+        for i in 0..3 {
+            let (n, _) = socket
+                .recv_from(&mut buf)
+                .unwrap_or_else(|_| panic!("datagram {i} delivered before flush returned"));
+            assert_eq!(&buf[..n], format!("line{i}").as_bytes());
+        }
+    }
+
+    #[test]
+    fn flush_blocks_for_space_under_ignore_strategy_instead_of_being_silently_dropped() {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        sender
+            .try_send(ChannelMsg::Msg(arrvec(b"already-queued")))
+            .expect("fill the one channel slot");
+
+        let writer = AsyncLogWriter::<256> {
+            formatter: v5424::Formatter::new(Facility::User.code(), None, "test".to_string(), 1),
+            sender,
+            worker: None,
+            max_log_level: log::LevelFilter::Info,
+            level_to_severity: default_level_to_severity,
+            full_buffer_error_strategy: FullBufferErrorStrategy::Ignore,
+        };
+
+        let drainer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            receiver.recv().expect("drain the pre-queued message");
+            match receiver.recv().expect("drain the flush request") {
+                ChannelMsg::Flush(ack) => ack.send(()).expect("send the flush ack"),
+                _ => panic!("expected the queued message to be a Flush request"),
+            }
+        });
+
+        let started = Instant::now();
+        writer.flush().expect(
+            "a full channel must not silently drop the flush request under FullBufferErrorStrategy::Ignore",
+        );
+        assert!(
+            started.elapsed() >= Duration::from_millis(40),
+            "flush must block until the worker drains space for it, not return immediately"
+        );
+
+        drainer.join().expect("drainer thread");
+    }
+
+    #[test]
+    fn drop_sends_quit_and_joins_the_worker_before_returning() {
+        let (_socket, addr) = recv_socket();
+        let writer = test_writer(addr, 8, FullBufferErrorStrategy::Ignore);
+        let sender = writer.sender.clone();
+
+        drop(writer);
+
+        // Drop's join is synchronous, so by the time it returns the worker thread has already
+        // exited and dropped its end of the channel.
+        assert!(matches!(sender.send(ChannelMsg::Quit), Err(SendError(_))));
+    }
+
+    #[test]
+    fn fail_strategy_blocks_for_space_instead_of_dropping() {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        sender
+            .try_send(ChannelMsg::Msg(arrvec(b"already-queued")))
+            .expect("fill the one channel slot");
+
+        let writer = AsyncLogWriter::<256> {
+            formatter: v5424::Formatter::new(Facility::User.code(), None, "test".to_string(), 1),
+            sender,
+            worker: None,
+            max_log_level: log::LevelFilter::Info,
+            level_to_severity: default_level_to_severity,
+            full_buffer_error_strategy: FullBufferErrorStrategy::Fail,
+        };
+
+        let drainer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            receiver.recv().expect("drain the pre-queued message");
+        });
+
+        let started = Instant::now();
+        writer
+            .send(arrvec(b"new"))
+            .expect("Fail blocks instead of erroring once space frees up");
+        assert!(
+            started.elapsed() >= Duration::from_millis(40),
+            "Fail must block waiting for space rather than return immediately"
+        );
+
+        drainer.join().expect("drainer thread");
+    }
+}
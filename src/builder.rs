@@ -0,0 +1,362 @@
+//! Fluent construction of a [LogWriter](crate::LogWriter).
+use syslog_fmt::v5424;
+use syslog_net::Transport;
+
+use crate::{
+    filter::FilterSpec,
+    log_writer::{BrokenPipeErrorStrategy, FullBufferErrorStrategy},
+    default_level_to_severity, AsyncLogWriter, FormatFn, LevelToSeverity, LogWriter,
+    StructuredDataConfig,
+};
+
+/// The default capacity, in bytes, of the formatting buffer backing a [LogWriter].
+const DEFAULT_CAP: usize = 2048;
+
+/// Builds a [LogWriter] by assembling the formatter, transport, and error-handling policy.
+pub struct Builder {
+    max_log_level: log::LevelFilter,
+    level_to_severity: LevelToSeverity,
+    full_buffer_error_strategy: FullBufferErrorStrategy,
+    broken_pipe_error_strategy: BrokenPipeErrorStrategy,
+    structured_data: Option<StructuredDataConfig>,
+    filter_spec: Option<FilterSpec>,
+    ring_buffer: Option<(usize, log::LevelFilter)>,
+    format_fn: Option<FormatFn>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            max_log_level: log::LevelFilter::Info,
+            level_to_severity: default_level_to_severity,
+            full_buffer_error_strategy: FullBufferErrorStrategy::Ignore,
+            broken_pipe_error_strategy: BrokenPipeErrorStrategy::Ignore,
+            structured_data: None,
+            filter_spec: None,
+            ring_buffer: None,
+            format_fn: None,
+        }
+    }
+
+    /// Sets the maximum log level to allow through to syslog.
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Overrides the mapping from [log::Level] to [syslog_fmt::Severity].
+    pub fn level_to_severity(mut self, level_to_severity: LevelToSeverity) -> Self {
+        self.level_to_severity = level_to_severity;
+        self
+    }
+
+    /// Sets how a full formatting buffer should be handled.
+    pub fn full_buffer_error_strategy(mut self, strategy: FullBufferErrorStrategy) -> Self {
+        self.full_buffer_error_strategy = strategy;
+        self
+    }
+
+    /// Sets how a broken transport pipe should be handled.
+    pub fn broken_pipe_error_strategy(mut self, strategy: BrokenPipeErrorStrategy) -> Self {
+        self.broken_pipe_error_strategy = strategy;
+        self
+    }
+
+    /// Renders each record's [log::kv] pairs into an RFC 5424 STRUCTURED-DATA element reported
+    /// under the SD-ID `sd_id@enterprise_id`, instead of discarding them.
+    pub fn structured_data(mut self, sd_id: impl Into<String>, enterprise_id: u32) -> Self {
+        self.structured_data = Some(StructuredDataConfig::new(sd_id, enterprise_id));
+        self
+    }
+
+    /// Configures per-target level filtering from an `env_logger`-style directive string, e.g.
+    /// `"info,base=debug,base::syslog=error"`. Once set, it replaces `max_log_level` for every
+    /// target: targets matched by no directive get the spec's bare default level, or `Off` if the
+    /// spec has none.
+    pub fn filter_spec(mut self, spec: &str) -> Self {
+        self.filter_spec = Some(FilterSpec::parse(spec));
+        self
+    }
+
+    /// Retains formatted lines up to `capacity` bytes in an in-memory ring buffer, gated by
+    /// `buffer_log_level` independent of `max_log_level`/`filter_spec`.
+    pub fn ring_buffer(mut self, capacity: usize, buffer_log_level: log::LevelFilter) -> Self {
+        self.ring_buffer = Some((capacity, buffer_log_level));
+        self
+    }
+
+    /// Overrides how each record's message body (the MSG portion of the v5424 envelope) is
+    /// rendered, in place of [default_format_fn](crate::default_format_fn). Lets callers inject
+    /// the target, module path, file/line, or a custom layout into the syslog message.
+    pub fn format_fn(
+        mut self,
+        format_fn: impl Fn(&mut dyn std::io::Write, &flexi_logger::DeferredNow, &flexi_logger::Record<'_>) -> std::io::Result<()>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.format_fn = Some(Box::new(format_fn));
+        self
+    }
+
+    /// Builds a [LogWriter] with the default buffer capacity.
+    pub fn build(self, formatter: v5424::Formatter, transport: Transport) -> LogWriter<DEFAULT_CAP> {
+        self.build_with_capacity(formatter, transport)
+    }
+
+    /// Builds a [LogWriter] with an explicit buffer capacity.
+    pub fn build_with_capacity<const CAP: usize>(
+        self,
+        formatter: v5424::Formatter,
+        transport: Transport,
+    ) -> LogWriter<CAP> {
+        let writer = LogWriter::new(
+            formatter,
+            transport,
+            self.max_log_level,
+            self.level_to_severity,
+            self.full_buffer_error_strategy,
+            self.broken_pipe_error_strategy,
+        );
+
+        let writer = match self.structured_data {
+            Some(config) => writer.with_structured_data(config),
+            None => writer,
+        };
+
+        let writer = match self.filter_spec {
+            Some(filter_spec) => writer.with_filter_spec(filter_spec),
+            None => writer,
+        };
+
+        let writer = match self.ring_buffer {
+            Some((capacity, buffer_log_level)) => writer.with_ring_buffer(capacity, buffer_log_level),
+            None => writer,
+        };
+
+        match self.format_fn {
+            Some(format_fn) => writer.with_format_fn(format_fn),
+            None => writer,
+        }
+    }
+
+    /// Builds a [Builder] from a deserialized [Config](crate::Config), applying its level,
+    /// filter, and error-strategy settings. The facility, formatter fields, and transport are
+    /// consumed by [Config::build](crate::Config::build) rather than the [Builder] itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `config.max_log_level` isn't a valid [log::LevelFilter].
+    #[cfg(feature = "serde")]
+    pub fn from_config(config: &crate::Config) -> std::io::Result<Self> {
+        let max_log_level = config.max_log_level.parse().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid max_log_level")
+        })?;
+
+        let mut builder = Self::new()
+            .max_log_level(max_log_level)
+            .full_buffer_error_strategy(config.full_buffer_error_strategy)
+            .broken_pipe_error_strategy(config.broken_pipe_error_strategy);
+
+        if let Some(spec) = &config.filter_spec {
+            builder = builder.filter_spec(spec);
+        }
+
+        Ok(builder)
+    }
+
+    /// Builds a [LibcLogWriter](crate::LibcLogWriter) that sends records to the local syslog
+    /// daemon via `openlog`/`syslog` instead of a [Transport] socket.
+    #[cfg(all(unix, feature = "libc-transport"))]
+    pub fn build_libc<const CAP: usize>(
+        self,
+        ident: &str,
+        facility: crate::Facility,
+    ) -> crate::LibcLogWriter<CAP> {
+        let writer = crate::LibcLogWriter::new(
+            ident,
+            facility,
+            self.max_log_level,
+            self.level_to_severity,
+            self.full_buffer_error_strategy,
+        );
+
+        match self.filter_spec {
+            Some(filter_spec) => writer.with_filter_spec(filter_spec),
+            None => writer,
+        }
+    }
+
+    /// Builds an [AsyncLogWriter] instead of a [LogWriter]: formatting still happens on the
+    /// caller's thread, but the blocking [Transport] I/O is handed off to a background worker
+    /// thread over a channel bounded to `capacity` messages.
+    ///
+    /// # Panics
+    ///
+    /// [AsyncLogWriter] has no equivalent of `structured_data`, `filter_spec`, `ring_buffer`, or
+    /// `format_fn` (see its type docs), so this panics if any of those were already configured on
+    /// `self` rather than silently dropping them.
+    pub fn async_mode(self, capacity: usize) -> AsyncModeBuilder {
+        assert!(
+            self.structured_data.is_none()
+                && self.filter_spec.is_none()
+                && self.ring_buffer.is_none()
+                && self.format_fn.is_none(),
+            "async_mode() does not support structured_data/filter_spec/ring_buffer/format_fn; \
+             AsyncLogWriter only honors max_log_level, level_to_severity, and \
+             full_buffer_error_strategy"
+        );
+        AsyncModeBuilder {
+            builder: self,
+            capacity,
+        }
+    }
+}
+
+/// Builder returned by [Builder::async_mode], carrying the channel capacity through to
+/// [AsyncModeBuilder::build].
+pub struct AsyncModeBuilder {
+    builder: Builder,
+    capacity: usize,
+}
+
+impl AsyncModeBuilder {
+    /// Builds an [AsyncLogWriter] with the default formatting buffer capacity.
+    pub fn build(self, formatter: v5424::Formatter, transport: Transport) -> AsyncLogWriter<DEFAULT_CAP> {
+        self.build_with_capacity(formatter, transport)
+    }
+
+    /// Builds an [AsyncLogWriter] with an explicit formatting buffer capacity.
+    pub fn build_with_capacity<const CAP: usize>(
+        self,
+        formatter: v5424::Formatter,
+        transport: Transport,
+    ) -> AsyncLogWriter<CAP> {
+        AsyncLogWriter::new(
+            formatter,
+            transport,
+            self.capacity,
+            self.builder.max_log_level,
+            self.builder.level_to_severity,
+            self.builder.full_buffer_error_strategy,
+        )
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::UdpSocket, time::Duration};
+
+    use flexi_logger::{writers::LogWriter as _, DeferredNow};
+
+    use super::*;
+    use crate::Facility;
+
+    fn recv_socket() -> (UdpSocket, std::net::SocketAddr) {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("bind recv socket");
+        socket
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .expect("set read timeout");
+        let addr = socket.local_addr().expect("local addr");
+        (socket, addr)
+    }
+
+    fn test_formatter() -> v5424::Formatter {
+        v5424::Formatter::new(Facility::User.code(), None, "test".to_string(), 1)
+    }
+
+    fn test_record() -> log::Record<'static> {
+        log::Record::builder()
+            .level(log::Level::Info)
+            .target("test")
+            .args(format_args!("hello world"))
+            .build()
+    }
+
+    #[test]
+    fn build_produces_a_working_log_writer() {
+        let (socket, addr) = recv_socket();
+        let transport = Transport::udp(addr).expect("build udp transport");
+        let writer = Builder::new().build(test_formatter(), transport);
+
+        writer
+            .write(&mut DeferredNow::new(), &test_record())
+            .expect("write succeeds");
+
+        let mut buf = [0u8; 256];
+        let (n, _) = socket.recv_from(&mut buf).expect("datagram received");
+        assert!(String::from_utf8_lossy(&buf[..n]).contains("hello world"));
+    }
+
+    #[test]
+    fn build_with_capacity_honors_an_explicit_buffer_size() {
+        let (socket, addr) = recv_socket();
+        let transport = Transport::udp(addr).expect("build udp transport");
+        let writer = Builder::new().build_with_capacity::<64>(test_formatter(), transport);
+
+        writer
+            .write(&mut DeferredNow::new(), &test_record())
+            .expect("write succeeds");
+
+        let mut buf = [0u8; 256];
+        let (n, _) = socket.recv_from(&mut buf).expect("datagram received");
+        assert!(String::from_utf8_lossy(&buf[..n]).contains("hello world"));
+    }
+
+    #[test]
+    fn async_mode_build_produces_a_working_async_log_writer() {
+        let (socket, addr) = recv_socket();
+        let transport = Transport::udp(addr).expect("build udp transport");
+        let writer = Builder::new().async_mode(8).build(test_formatter(), transport);
+
+        writer
+            .write(&mut DeferredNow::new(), &test_record())
+            .expect("write succeeds");
+        writer.flush().expect("flush blocks until the worker acks");
+
+        let mut buf = [0u8; 256];
+        let (n, _) = socket.recv_from(&mut buf).expect("datagram received");
+        assert!(String::from_utf8_lossy(&buf[..n]).contains("hello world"));
+    }
+
+    #[test]
+    #[should_panic(expected = "async_mode() does not support")]
+    fn async_mode_panics_if_structured_data_was_already_configured() {
+        Builder::new().structured_data("ex", 1).async_mode(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "async_mode() does not support")]
+    fn async_mode_panics_if_filter_spec_was_already_configured() {
+        Builder::new().filter_spec("info").async_mode(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "async_mode() does not support")]
+    fn async_mode_panics_if_ring_buffer_was_already_configured() {
+        Builder::new()
+            .ring_buffer(1024, log::LevelFilter::Info)
+            .async_mode(8);
+    }
+
+    #[test]
+    #[should_panic(expected = "async_mode() does not support")]
+    fn async_mode_panics_if_format_fn_was_already_configured() {
+        Builder::new()
+            .format_fn(|w, _now, record| write!(w, "{}", record.args()))
+            .async_mode(8);
+    }
+
+    #[cfg(all(unix, feature = "libc-transport"))]
+    #[test]
+    fn build_libc_produces_a_writer_that_can_be_dropped_cleanly() {
+        let writer = Builder::new().build_libc::<256>("flexi-syslog-builder-test", Facility::User);
+        drop(writer);
+    }
+}
@@ -0,0 +1,172 @@
+//! Serde-deserializable configuration for assembling a [Builder]/[LogWriter] from TOML/JSON/YAML
+//! instead of hand-assembling a [v5424::Formatter], [Transport], and [Builder] in code.
+//!
+//! Behind the `serde` feature.
+use std::{io, net::SocketAddr, path::PathBuf};
+
+use syslog_fmt::v5424;
+use syslog_net::Transport;
+
+use crate::{
+    log_writer::{BrokenPipeErrorStrategy, FullBufferErrorStrategy},
+    Builder, Facility, LogWriter,
+};
+
+/// Everything needed to construct a [LogWriter], loaded from e.g. a TOML/JSON/YAML file.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Config {
+    /// The maximum log level to allow through to syslog, e.g. `"info"`. Defaults to `"info"`.
+    #[serde(default = "default_max_log_level")]
+    pub max_log_level: String,
+    /// An `env_logger`-style per-target directive string, e.g. `"info,base=debug"`. When set, it
+    /// replaces `max_log_level` for every target: targets matched by no directive get the spec's
+    /// bare default level, or `off` if the spec has none.
+    #[serde(default)]
+    pub filter_spec: Option<String>,
+    /// The RFC 5424 facility to report records under. Defaults to [Facility::User].
+    #[serde(default)]
+    pub facility: Facility,
+    /// The APP-NAME field of the v5424 header, e.g. the binary's name.
+    pub app_name: String,
+    /// The HOSTNAME field of the v5424 header. Defaults to the NILVALUE (`-`) if unset.
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// The PROCID field of the v5424 header. Defaults to the current process ID if unset.
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// Where to send formatted syslog lines.
+    pub transport: TransportConfig,
+    #[serde(default)]
+    pub full_buffer_error_strategy: FullBufferErrorStrategy,
+    #[serde(default)]
+    pub broken_pipe_error_strategy: BrokenPipeErrorStrategy,
+}
+
+fn default_max_log_level() -> String {
+    "info".to_string()
+}
+
+/// Selects and configures a [Transport] backend.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum TransportConfig {
+    Udp { address: SocketAddr },
+    Tcp { address: SocketAddr },
+    Unix { path: PathBuf },
+}
+
+impl TransportConfig {
+    fn build(&self) -> io::Result<Transport> {
+        match self {
+            TransportConfig::Udp { address } => Transport::udp(*address),
+            TransportConfig::Tcp { address } => Transport::tcp(*address),
+            TransportConfig::Unix { path } => Transport::unix(path),
+        }
+    }
+}
+
+impl Config {
+    /// Assembles the [v5424::Formatter] and [Transport] this config describes, and builds a
+    /// [LogWriter] with the default buffer capacity.
+    pub fn build(self) -> io::Result<LogWriter<2048>> {
+        let formatter = v5424::Formatter::new(
+            self.facility.code(),
+            self.hostname.clone(),
+            self.app_name.clone(),
+            self.pid.unwrap_or_else(std::process::id),
+        );
+        let transport = self.transport.build()?;
+        let builder = Builder::from_config(&self)?;
+
+        Ok(builder.build(formatter, transport))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let config = Config {
+            max_log_level: "debug".to_string(),
+            filter_spec: Some("info,base=debug".to_string()),
+            facility: Facility::Local0,
+            app_name: "myapp".to_string(),
+            hostname: Some("myhost".to_string()),
+            pid: Some(42),
+            transport: TransportConfig::Tcp {
+                address: "127.0.0.1:514".parse().unwrap(),
+            },
+            full_buffer_error_strategy: FullBufferErrorStrategy::Fail,
+            broken_pipe_error_strategy: BrokenPipeErrorStrategy::Fail,
+        };
+
+        let json = serde_json::to_string(&config).expect("serializes");
+        let round_tripped: Config = serde_json::from_str(&json).expect("deserializes");
+        assert_eq!(round_tripped, config);
+    }
+
+    #[test]
+    fn defaults_fill_in_optional_fields() {
+        let json = r#"{
+            "app_name": "myapp",
+            "transport": { "kind": "unix", "path": "/dev/log" }
+        }"#;
+
+        let config: Config = serde_json::from_str(json).expect("deserializes");
+        assert_eq!(config.max_log_level, "info");
+        assert_eq!(config.filter_spec, None);
+        assert_eq!(config.facility, Facility::User);
+        assert_eq!(config.hostname, None);
+        assert_eq!(config.pid, None);
+        assert_eq!(config.full_buffer_error_strategy, FullBufferErrorStrategy::Ignore);
+        assert_eq!(config.broken_pipe_error_strategy, BrokenPipeErrorStrategy::Ignore);
+    }
+
+    #[test]
+    fn build_honors_max_log_level_and_filter_spec_from_the_config() {
+        use flexi_logger::writers::LogWriter as _;
+
+        let config = Config {
+            max_log_level: "error".to_string(),
+            filter_spec: Some("error,base=debug".to_string()),
+            facility: Facility::User,
+            app_name: "myapp".to_string(),
+            hostname: None,
+            pid: None,
+            transport: TransportConfig::Udp {
+                address: "127.0.0.1:9".parse().unwrap(),
+            },
+            full_buffer_error_strategy: FullBufferErrorStrategy::Ignore,
+            broken_pipe_error_strategy: BrokenPipeErrorStrategy::Ignore,
+        };
+
+        let writer = config.build().expect("builds with a valid max_log_level");
+        assert_eq!(
+            writer.max_log_level(),
+            log::LevelFilter::Debug,
+            "the writer's max_log_level should widen to the filter_spec's most verbose rule"
+        );
+    }
+
+    #[test]
+    fn build_rejects_an_invalid_max_log_level() {
+        let config = Config {
+            max_log_level: "not-a-level".to_string(),
+            filter_spec: None,
+            facility: Facility::User,
+            app_name: "myapp".to_string(),
+            hostname: None,
+            pid: None,
+            transport: TransportConfig::Udp {
+                address: "127.0.0.1:9".parse().unwrap(),
+            },
+            full_buffer_error_strategy: FullBufferErrorStrategy::Ignore,
+            broken_pipe_error_strategy: BrokenPipeErrorStrategy::Ignore,
+        };
+
+        assert!(config.build().is_err());
+    }
+}
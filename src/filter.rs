@@ -0,0 +1,135 @@
+//! Per-target level filtering, parsed from `env_logger`-style directive strings such as
+//! `"info,base=debug,base::syslog=error"`.
+use log::LevelFilter;
+
+/// A parsed set of per-target level directives plus a default level for targets that match
+/// none of them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilterSpec {
+    /// `(target, level)` rules, sorted by `target` length descending so the first match found
+    /// by [FilterSpec::level_for] is always the longest (most specific) one.
+    directives: Vec<(String, LevelFilter)>,
+    default_level: LevelFilter,
+}
+
+impl FilterSpec {
+    /// Parses a comma-separated directive string. Each comma-separated part is either a bare
+    /// level (sets the default) or a `target=level` rule. Unparseable parts are ignored.
+    ///
+    /// ```text
+    /// "info,base=debug,base::syslog=error"
+    /// ```
+    pub fn parse(spec: &str) -> Self {
+        let mut directives = Vec::new();
+        let mut default_level = LevelFilter::Off;
+
+        for part in spec.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    if let Some(level) = parse_level(level.trim()) {
+                        directives.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Some(level) = parse_level(part) {
+                        default_level = level;
+                    }
+                }
+            }
+        }
+
+        // Longest target first, so `level_for` can stop at the first (most specific) match.
+        directives.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+
+        Self {
+            directives,
+            default_level,
+        }
+    }
+
+    /// Returns the level allowed for `target`, matching the longest directive whose target is a
+    /// `::`-delimited module-path prefix of `target`, falling back to the default level.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|(prefix, _)| is_module_prefix(target, prefix))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default_level)
+    }
+
+    /// The coarsest (most verbose) level across every rule and the default, so that a level
+    /// filter upstream of this [FilterSpec] (e.g. flexi-logger's own pre-filtering) doesn't
+    /// drop records this spec still wants to see.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default_level, LevelFilter::max)
+    }
+}
+
+fn parse_level(s: &str) -> Option<LevelFilter> {
+    s.parse().ok()
+}
+
+/// Whether `prefix` is `target` itself or a `::`-delimited module-path prefix of it, e.g.
+/// `"base"` matches `"base"` and `"base::net"` but not `"basement"`.
+fn is_module_prefix(target: &str, prefix: &str) -> bool {
+    target == prefix
+        || target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_level_sets_default() {
+        let spec = FilterSpec::parse("info");
+        assert_eq!(spec.level_for("anything"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn target_rules_override_default() {
+        let spec = FilterSpec::parse("info,base=debug,base::syslog=error");
+        assert_eq!(spec.level_for("base"), LevelFilter::Debug);
+        assert_eq!(spec.level_for("base::net"), LevelFilter::Debug);
+        assert_eq!(spec.level_for("base::syslog"), LevelFilter::Error);
+        assert_eq!(spec.level_for("base::syslog::writer"), LevelFilter::Error);
+        assert_eq!(spec.level_for("other"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn longest_prefix_wins_regardless_of_spec_order() {
+        let spec = FilterSpec::parse("base::syslog=error,base=debug");
+        assert_eq!(spec.level_for("base::syslog::writer"), LevelFilter::Error);
+    }
+
+    #[test]
+    fn max_level_is_the_most_verbose_rule() {
+        let spec = FilterSpec::parse("info,base=debug,base::syslog=error");
+        assert_eq!(spec.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn unparseable_parts_are_ignored() {
+        let spec = FilterSpec::parse("info,garbage,base=nonsense,base::net=warn");
+        assert_eq!(spec.level_for("base"), LevelFilter::Info);
+        assert_eq!(spec.level_for("base::net"), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn does_not_match_on_raw_string_prefix() {
+        let spec = FilterSpec::parse("info,base=debug");
+        assert_eq!(spec.level_for("basement::anything"), LevelFilter::Info);
+        assert_eq!(spec.level_for("base"), LevelFilter::Debug);
+        assert_eq!(spec.level_for("base::net"), LevelFilter::Debug);
+    }
+}
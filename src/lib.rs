@@ -0,0 +1,55 @@
+//! Adapts [flexi_logger] records to syslog, formatting them per RFC 5424 and shipping them
+//! through one of the [syslog_net::Transport] backends.
+mod async_writer;
+mod builder;
+#[cfg(feature = "serde")]
+mod config;
+mod facility;
+mod filter;
+#[cfg(all(unix, feature = "libc-transport"))]
+mod libc_transport;
+mod log_writer;
+mod ring_buffer;
+mod structured_data;
+
+pub use async_writer::AsyncLogWriter;
+pub use builder::Builder;
+#[cfg(feature = "serde")]
+pub use config::{Config, TransportConfig};
+pub use facility::Facility;
+pub use filter::FilterSpec;
+#[cfg(all(unix, feature = "libc-transport"))]
+pub use libc_transport::LibcLogWriter;
+pub use log_writer::{BrokenPipeErrorStrategy, FullBufferErrorStrategy, LogWriter};
+pub use structured_data::StructuredDataConfig;
+
+/// Maps a [log::Level] to the [syslog_fmt::Severity] carried in the syslog PRI field.
+pub type LevelToSeverity = fn(log::Level) -> syslog_fmt::Severity;
+
+/// The default [LevelToSeverity] mapping, following the usual log-level/syslog-severity
+/// convention.
+pub fn default_level_to_severity(level: log::Level) -> syslog_fmt::Severity {
+    match level {
+        log::Level::Error => syslog_fmt::Severity::Error,
+        log::Level::Warn => syslog_fmt::Severity::Warning,
+        log::Level::Info => syslog_fmt::Severity::Info,
+        log::Level::Debug | log::Level::Trace => syslog_fmt::Severity::Debug,
+    }
+}
+
+/// Renders a record's message body (the MSG portion of the v5424 envelope) into `w`. Set through
+/// [Builder::format_fn]; defaults to [default_format_fn], i.e. writing `record.args()`.
+pub type FormatFn = Box<
+    dyn Fn(&mut dyn std::io::Write, &flexi_logger::DeferredNow, &flexi_logger::Record<'_>) -> std::io::Result<()>
+        + Send
+        + Sync,
+>;
+
+/// The default [FormatFn]: writes `record.args()` verbatim, today's (pre-`format_fn`) behavior.
+pub fn default_format_fn(
+    w: &mut dyn std::io::Write,
+    _now: &flexi_logger::DeferredNow,
+    record: &flexi_logger::Record<'_>,
+) -> std::io::Result<()> {
+    write!(w, "{}", record.args())
+}
@@ -0,0 +1,198 @@
+//! A [LogWriter](crate::LogWriter) variant that talks to the local syslog daemon through the
+//! POSIX C API (`openlog`/`syslog`/`closelog`) instead of a [Transport](syslog_net::Transport)
+//! socket, letting the platform handle framing and reconnection.
+//!
+//! Only available on `cfg(unix)` behind the `libc-transport` feature.
+use std::{
+    ffi::CString,
+    fmt,
+    io::{self, ErrorKind},
+    sync::Mutex,
+};
+
+use arrayvec::ArrayVec;
+use flexi_logger::{DeferredNow, Record};
+use syslog_fmt::Severity;
+
+use crate::{filter::FilterSpec, log_writer::FullBufferErrorStrategy, Facility, LevelToSeverity};
+
+/// `syslog(3)`'s facility codes are the RFC 5424 facility number shifted into the high bits of
+/// the priority, matching `LOG_MAKEPRI` in `<sys/syslog.h>`.
+fn facility_as_raw(facility: Facility) -> libc::c_int {
+    (facility.code() as libc::c_int) << 3
+}
+
+fn severity_as_raw(severity: Severity) -> libc::c_int {
+    match severity {
+        Severity::Emergency => libc::LOG_EMERG,
+        Severity::Alert => libc::LOG_ALERT,
+        Severity::Critical => libc::LOG_CRIT,
+        Severity::Error => libc::LOG_ERR,
+        Severity::Warning => libc::LOG_WARNING,
+        Severity::Notice => libc::LOG_NOTICE,
+        Severity::Info => libc::LOG_INFO,
+        Severity::Debug => libc::LOG_DEBUG,
+    }
+}
+
+/// Writes [records](flexi_logger::Record) to the local syslog daemon via `openlog`/`syslog` from
+/// `libc`, instead of through a [Transport](syslog_net::Transport) socket.
+///
+/// Unlike [LogWriter](crate::LogWriter), records never pass through a v5424 formatter envelope
+/// here — `syslog(3)` fills in the timestamp/hostname/PID itself — so
+/// [StructuredDataConfig](crate::StructuredDataConfig) (which renders into that envelope) has no
+/// equivalent on this writer, nor is there a [FormatFn](crate::FormatFn) hook: the message body
+/// is always `record.args()` verbatim. `filter_spec` is still honored, since it's orthogonal to
+/// the transport.
+pub struct LibcLogWriter<const CAP: usize> {
+    facility: Facility,
+    buf: Mutex<ArrayVec<u8, CAP>>,
+    max_log_level: log::LevelFilter,
+    level_to_severity: LevelToSeverity,
+    full_buffer_error_strategy: FullBufferErrorStrategy,
+    /// When set, overrides `max_log_level` with per-target directives, e.g.
+    /// `"info,base=debug,base::syslog=error"`.
+    filter_spec: Option<FilterSpec>,
+}
+
+impl<const CAP: usize> LibcLogWriter<CAP> {
+    /// Calls `openlog(ident, LOG_PID, facility)`, registering this process with the local
+    /// syslog daemon under `ident`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ident` contains an interior NUL byte.
+    pub fn new(
+        ident: &str,
+        facility: Facility,
+        max_log_level: log::LevelFilter,
+        level_to_severity: LevelToSeverity,
+        full_buffer_error_strategy: FullBufferErrorStrategy,
+    ) -> Self {
+        let ident = CString::new(ident).expect("syslog ident must not contain a NUL byte");
+        // `openlog` retains the pointer for the lifetime of the process, so we intentionally
+        // leak it rather than let the CString drop at the end of this scope.
+        unsafe { libc::openlog(ident.into_raw(), libc::LOG_PID, facility_as_raw(facility)) };
+
+        Self {
+            facility,
+            buf: Mutex::new(ArrayVec::new()),
+            max_log_level,
+            level_to_severity,
+            full_buffer_error_strategy,
+            filter_spec: None,
+        }
+    }
+
+    /// Enables per-target level filtering. `filter_spec` then replaces `max_log_level` for every
+    /// target: targets matched by none of its directives get its default level.
+    pub fn with_filter_spec(mut self, filter_spec: FilterSpec) -> Self {
+        self.filter_spec = Some(filter_spec);
+        self
+    }
+
+    /// The level allowed through for `target`, honoring [FilterSpec] directives if configured.
+    fn level_for(&self, target: &str) -> log::LevelFilter {
+        match &self.filter_spec {
+            Some(filter_spec) => filter_spec.level_for(target),
+            None => self.max_log_level,
+        }
+    }
+}
+
+impl<const CAP: usize> fmt::Debug for LibcLogWriter<CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LibcLogWriter")
+            .field("max_log_level", &self.max_log_level)
+            .finish()
+    }
+}
+
+impl<const CAP: usize> flexi_logger::writers::LogWriter for LibcLogWriter<CAP> {
+    fn write(&self, _now: &mut DeferredNow, record: &Record<'_>) -> io::Result<()> {
+        use std::io::Write;
+
+        if record.level() > self.level_for(record.target()) {
+            return Ok(());
+        }
+
+        let severity = (self.level_to_severity)(record.level());
+        let priority = facility_as_raw(self.facility) | severity_as_raw(severity);
+
+        let mut buf = self.buf.lock().unwrap_or_else(|e| e.into_inner());
+        buf.clear();
+
+        if let Err(e) = write!(buf, "{}", record.args()) {
+            if e.kind() != ErrorKind::WriteZero {
+                match self.full_buffer_error_strategy {
+                    FullBufferErrorStrategy::Ignore => (),
+                    FullBufferErrorStrategy::Fail => return Err(e),
+                }
+            }
+        }
+        // `write!` may have filled `buf` to exactly `CAP` bytes (on overflow it truncates and
+        // reports `WriteZero`, which is ignored above); make room for the NUL terminator so the
+        // following `push` can't panic.
+        if buf.is_full() {
+            buf.pop();
+        }
+        buf.push(0);
+
+        let message = CString::from_vec_with_nul(buf.to_vec())
+            .unwrap_or_else(|_| CString::new("<invalid syslog message>").unwrap());
+        let format = CString::new("%s").expect("static format string has no NUL bytes");
+
+        unsafe { libc::syslog(priority, format.as_ptr(), message.as_ptr()) };
+
+        Ok(())
+    }
+
+    /// A no-op: `syslog(3)` talks to the local daemon directly, so there's no pipe to break.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        match &self.filter_spec {
+            Some(filter_spec) => self.max_log_level.max(filter_spec.max_level()),
+            None => self.max_log_level,
+        }
+    }
+}
+
+impl<const CAP: usize> Drop for LibcLogWriter<CAP> {
+    fn drop(&mut self) {
+        unsafe { libc::closelog() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facility_as_raw_shifts_code_into_high_bits() {
+        assert_eq!(facility_as_raw(Facility::Kern), libc::LOG_KERN);
+        assert_eq!(facility_as_raw(Facility::User), libc::LOG_USER);
+        assert_eq!(facility_as_raw(Facility::Local0), libc::LOG_LOCAL0);
+        assert_eq!(facility_as_raw(Facility::Local7), libc::LOG_LOCAL7);
+    }
+
+    #[test]
+    fn severity_as_raw_matches_libc_constants() {
+        assert_eq!(severity_as_raw(Severity::Emergency), libc::LOG_EMERG);
+        assert_eq!(severity_as_raw(Severity::Alert), libc::LOG_ALERT);
+        assert_eq!(severity_as_raw(Severity::Critical), libc::LOG_CRIT);
+        assert_eq!(severity_as_raw(Severity::Error), libc::LOG_ERR);
+        assert_eq!(severity_as_raw(Severity::Warning), libc::LOG_WARNING);
+        assert_eq!(severity_as_raw(Severity::Notice), libc::LOG_NOTICE);
+        assert_eq!(severity_as_raw(Severity::Info), libc::LOG_INFO);
+        assert_eq!(severity_as_raw(Severity::Debug), libc::LOG_DEBUG);
+    }
+
+    #[test]
+    fn priority_combines_facility_and_severity_without_overlap() {
+        let priority = facility_as_raw(Facility::Local3) | severity_as_raw(Severity::Warning);
+        assert_eq!(priority, libc::LOG_LOCAL3 | libc::LOG_WARNING);
+    }
+}
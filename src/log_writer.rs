@@ -1,8 +1,10 @@
 //! The LogWriter that adapts flexi-logger log records to the syslog.
 use std::{
+    collections::HashSet,
     fmt,
     io::{self, ErrorKind},
     sync::Arc,
+    thread::ThreadId,
 };
 
 use arrayvec::ArrayVec;
@@ -11,16 +13,25 @@ use parking_lot::Mutex;
 use syslog_fmt::v5424;
 use syslog_net::Transport;
 
-use crate::LevelToSeverity;
+use crate::{
+    filter::FilterSpec, ring_buffer::RingBuffer, structured_data::StructuredDataConfig, FormatFn,
+    LevelToSeverity,
+};
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FullBufferErrorStrategy {
+    #[default]
     Ignore,
     Fail,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum BrokenPipeErrorStrategy {
+    #[default]
     Ignore,
     Fail,
 }
@@ -44,11 +55,52 @@ pub struct LogWriter<const CAP: usize> {
     full_buffer_error_strategy: FullBufferErrorStrategy,
     /// How should a broken pipe be handled
     broken_strategy_error_strategy: BrokenPipeErrorStrategy,
+    /// When set, the record's [log::kv] pairs are rendered into an RFC 5424 STRUCTURED-DATA
+    /// element under this SD-ID instead of being discarded.
+    structured_data: Option<StructuredDataConfig>,
+    /// When set, overrides `max_log_level` with per-target directives, e.g.
+    /// `"info,base=debug,base::syslog=error"`.
+    filter_spec: Option<FilterSpec>,
+    /// The level gate for retaining a record in the ring buffer, independent of whether it's
+    /// also sent to the transport.
+    buffer_log_level: log::LevelFilter,
+    /// The ids of whichever threads are currently inside [LogWriter::extract]/
+    /// [LogWriter::snapshot], so that logging triggered from inside the extraction closure on
+    /// one of those *same* threads doesn't try to re-lock `buffered_transport` and deadlock.
+    /// Other threads, and concurrent extractions from different threads, are unaffected and
+    /// keep logging/extracting normally.
+    extracting: Mutex<HashSet<ThreadId>>,
+    /// Renders the MSG portion of the v5424 envelope. Defaults to [crate::default_format_fn].
+    format_fn: FormatFn,
 }
 
 struct BufferedTransport<const CAP: usize> {
     buf: ArrayVec<u8, CAP>,
     transport: Transport,
+    ring_buffer: Option<RingBuffer>,
+}
+
+/// Marks the current thread as "extracting" for the lifetime of the guard, so that
+/// [LogWriter::write] on this thread no-ops instead of re-locking `buffered_transport` and
+/// deadlocking. Removes the thread from that set on drop, including on unwind, so a panic inside
+/// [LogWriter::extract]'s closure can't leave the thread stuck.
+struct ExtractingGuard<'a> {
+    extracting: &'a Mutex<HashSet<ThreadId>>,
+    tid: ThreadId,
+}
+
+impl<'a> ExtractingGuard<'a> {
+    fn enter(extracting: &'a Mutex<HashSet<ThreadId>>) -> Self {
+        let tid = std::thread::current().id();
+        extracting.lock().insert(tid);
+        Self { extracting, tid }
+    }
+}
+
+impl Drop for ExtractingGuard<'_> {
+    fn drop(&mut self) {
+        self.extracting.lock().remove(&self.tid);
+    }
 }
 
 impl<const CAP: usize> LogWriter<CAP> {
@@ -63,11 +115,94 @@ impl<const CAP: usize> LogWriter<CAP> {
         let buf = ArrayVec::<_, CAP>::new();
         Self {
             formatter,
-            buffered_transport: Arc::new(Mutex::new(BufferedTransport { buf, transport })),
+            buffered_transport: Arc::new(Mutex::new(BufferedTransport {
+                buf,
+                transport,
+                ring_buffer: None,
+            })),
             max_log_level,
             level_to_severity,
             full_buffer_error_strategy,
             broken_strategy_error_strategy,
+            structured_data: None,
+            filter_spec: None,
+            buffer_log_level: log::LevelFilter::Off,
+            extracting: Mutex::new(HashSet::new()),
+            format_fn: Box::new(crate::default_format_fn),
+        }
+    }
+
+    /// Overrides how each record's message body is rendered, in place of [crate::default_format_fn].
+    pub fn with_format_fn(mut self, format_fn: FormatFn) -> Self {
+        self.format_fn = format_fn;
+        self
+    }
+
+    /// Enables rendering the record's [log::kv] pairs into an RFC 5424 STRUCTURED-DATA element.
+    pub fn with_structured_data(mut self, config: StructuredDataConfig) -> Self {
+        self.structured_data = Some(config);
+        self
+    }
+
+    /// Enables per-target level filtering. `filter_spec` then replaces `max_log_level` for every
+    /// target: targets matched by none of its directives get its default level.
+    pub fn with_filter_spec(mut self, filter_spec: FilterSpec) -> Self {
+        self.filter_spec = Some(filter_spec);
+        self
+    }
+
+    /// The level allowed through for `target`, honoring [FilterSpec] directives if configured.
+    fn level_for(&self, target: &str) -> log::LevelFilter {
+        match &self.filter_spec {
+            Some(filter_spec) => filter_spec.level_for(target),
+            None => self.max_log_level,
+        }
+    }
+
+    /// Enables retention of formatted lines in a bounded in-memory ring buffer, independent of
+    /// what's sent to the transport. `capacity` is the buffer size in bytes; `buffer_log_level`
+    /// is the level gate for retention.
+    pub fn with_ring_buffer(self, capacity: usize, buffer_log_level: log::LevelFilter) -> Self {
+        self.buffered_transport.lock().ring_buffer = Some(RingBuffer::new(capacity));
+        Self {
+            buffer_log_level,
+            ..self
+        }
+    }
+
+    /// Copies out the ring buffer's current contents without clearing it. Empty if no ring
+    /// buffer is configured.
+    pub fn snapshot(&self) -> Vec<u8> {
+        let _guard = ExtractingGuard::enter(&self.extracting);
+        self.buffered_transport
+            .lock()
+            .ring_buffer
+            .as_ref()
+            .map(RingBuffer::snapshot)
+            .unwrap_or_default()
+    }
+
+    /// Hands the ring buffer's current contents to `f`, then clears the buffer. While `f` runs,
+    /// records logged through this writer from *this same thread* (e.g. from within `f` itself)
+    /// are dropped rather than attempting to re-lock the buffer and deadlock. Logging from other
+    /// threads, including other threads concurrently inside `extract`/`snapshot`, is unaffected.
+    ///
+    /// The thread is removed from that drop set even if `f` panics, so a caller that catches the
+    /// panic and keeps reusing the thread (e.g. a thread pool) doesn't leave it permanently
+    /// unable to log through this writer.
+    pub fn extract(&self, f: impl FnOnce(&[u8])) {
+        let _guard = ExtractingGuard::enter(&self.extracting);
+        let mut buf_trans = self.buffered_transport.lock();
+        if let Some(ring_buffer) = buf_trans.ring_buffer.as_mut() {
+            f(&ring_buffer.snapshot());
+            ring_buffer.clear();
+        }
+    }
+
+    /// Clears the ring buffer, if configured.
+    pub fn clear(&self) {
+        if let Some(ring_buffer) = self.buffered_transport.lock().ring_buffer.as_mut() {
+            ring_buffer.clear();
         }
     }
 }
@@ -82,16 +217,49 @@ impl<const CAP: usize> fmt::Debug for LogWriter<CAP> {
 }
 
 impl<const CAP: usize> flexi_logger::writers::LogWriter for LogWriter<CAP> {
-    fn write(&self, _now: &mut DeferredNow, record: &Record<'_>) -> io::Result<()> {
+    fn write(&self, now: &mut DeferredNow, record: &Record<'_>) -> io::Result<()> {
+        if self
+            .extracting
+            .lock()
+            .contains(&std::thread::current().id())
+        {
+            return Ok(());
+        }
+
+        let send_allowed = record.level() <= self.level_for(record.target());
+        let buffer_allowed = record.level() <= self.buffer_log_level;
+        if !send_allowed && !buffer_allowed {
+            return Ok(());
+        }
+
         let mut buf_trans = self.buffered_transport.lock();
         let bt = &mut *buf_trans;
         let severity = (self.level_to_severity)(record.level());
 
         bt.buf.clear();
 
-        let res = self
-            .formatter
-            .format(&mut bt.buf, severity, record.args(), None);
+        let mut msg_buf = ArrayVec::<u8, CAP>::new();
+        if let Err(e) = (self.format_fn)(&mut msg_buf, now, record) {
+            if e.kind() != ErrorKind::WriteZero {
+                match self.full_buffer_error_strategy {
+                    FullBufferErrorStrategy::Ignore => (),
+                    FullBufferErrorStrategy::Fail => return Err(e),
+                }
+            }
+        }
+        let msg = String::from_utf8_lossy(&msg_buf);
+
+        let sd_element = self
+            .structured_data
+            .as_ref()
+            .and_then(|config| config.render(record.key_values()));
+
+        let res = self.formatter.format(
+            &mut bt.buf,
+            severity,
+            &format_args!("{}", msg),
+            sd_element.as_deref(),
+        );
 
         if let Err(e) = res {
             if e.kind() != ErrorKind::WriteZero {
@@ -102,14 +270,22 @@ impl<const CAP: usize> flexi_logger::writers::LogWriter for LogWriter<CAP> {
             }
         }
 
-        if let Err(e) = bt.transport.send(&bt.buf) {
-            if e.kind() != ErrorKind::BrokenPipe {
-                match self.broken_strategy_error_strategy {
-                    BrokenPipeErrorStrategy::Ignore => (),
-                    BrokenPipeErrorStrategy::Fail => return Err(e),
-                }
+        if buffer_allowed {
+            if let Some(ring_buffer) = bt.ring_buffer.as_mut() {
+                ring_buffer.push(&bt.buf);
             }
-        };
+        }
+
+        if send_allowed {
+            if let Err(e) = bt.transport.send(&bt.buf) {
+                if e.kind() != ErrorKind::BrokenPipe {
+                    match self.broken_strategy_error_strategy {
+                        BrokenPipeErrorStrategy::Ignore => (),
+                        BrokenPipeErrorStrategy::Fail => return Err(e),
+                    }
+                }
+            };
+        }
 
         Ok(())
     }
@@ -121,6 +297,105 @@ impl<const CAP: usize> flexi_logger::writers::LogWriter for LogWriter<CAP> {
     }
 
     fn max_log_level(&self) -> log::LevelFilter {
-        self.max_log_level
+        let max_level = match &self.filter_spec {
+            Some(filter_spec) => self.max_log_level.max(filter_spec.max_level()),
+            None => self.max_log_level,
+        };
+        max_level.max(self.buffer_log_level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::UdpSocket, time::Duration};
+
+    use flexi_logger::{writers::LogWriter as _, DeferredNow};
+
+    use super::*;
+    use crate::Facility;
+
+    fn recv_socket() -> (UdpSocket, std::net::SocketAddr) {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("bind recv socket");
+        socket
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .expect("set read timeout");
+        let addr = socket.local_addr().expect("local addr");
+        (socket, addr)
+    }
+
+    fn test_writer(addr: std::net::SocketAddr) -> LogWriter<256> {
+        let formatter = v5424::Formatter::new(Facility::User.code(), None, "test".to_string(), 1);
+        let transport = Transport::udp(addr).expect("build udp transport");
+        LogWriter::new(
+            formatter,
+            transport,
+            log::LevelFilter::Info,
+            crate::default_level_to_severity,
+            FullBufferErrorStrategy::Ignore,
+            BrokenPipeErrorStrategy::Ignore,
+        )
+    }
+
+    fn test_record() -> log::Record<'static> {
+        log::Record::builder()
+            .level(log::Level::Info)
+            .target("test")
+            .args(format_args!("hello world"))
+            .build()
+    }
+
+    #[test]
+    fn extract_removes_the_thread_from_extracting_even_if_f_panics() {
+        let (_socket, addr) = recv_socket();
+        let writer = test_writer(addr).with_ring_buffer(64, log::LevelFilter::Info);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            writer.extract(|_| panic!("boom"));
+        }));
+        assert!(result.is_err(), "the closure's panic should propagate");
+
+        assert!(
+            !writer
+                .extracting
+                .lock()
+                .contains(&std::thread::current().id()),
+            "the current thread must not be stuck marked as extracting after a panic"
+        );
+    }
+
+    #[test]
+    fn default_format_fn_writes_record_args_verbatim() {
+        let (socket, addr) = recv_socket();
+        let writer = test_writer(addr);
+        let mut now = DeferredNow::new();
+
+        writer.write(&mut now, &test_record()).expect("write succeeds");
+
+        let mut buf = [0u8; 256];
+        let (n, _) = socket.recv_from(&mut buf).expect("datagram received");
+        let sent = String::from_utf8_lossy(&buf[..n]);
+        assert!(
+            sent.contains("hello world"),
+            "expected the record's args in the syslog message, got {sent:?}"
+        );
+    }
+
+    #[test]
+    fn custom_format_fn_output_reaches_the_formatter() {
+        let (socket, addr) = recv_socket();
+        let writer = test_writer(addr).with_format_fn(Box::new(|w, _now, record| {
+            write!(w, "custom[{}]: {}", record.target(), record.args())
+        }));
+        let mut now = DeferredNow::new();
+
+        writer.write(&mut now, &test_record()).expect("write succeeds");
+
+        let mut buf = [0u8; 256];
+        let (n, _) = socket.recv_from(&mut buf).expect("datagram received");
+        let sent = String::from_utf8_lossy(&buf[..n]);
+        assert!(
+            sent.contains("custom[test]: hello world"),
+            "expected the custom format_fn's output in the syslog message, got {sent:?}"
+        );
     }
 }
@@ -0,0 +1,101 @@
+//! A bounded in-memory ring buffer of formatted syslog lines, for retention independent of
+//! whatever a [Transport](syslog_net::Transport) actually ships.
+use std::collections::VecDeque;
+
+/// Retains the most recent formatted lines up to `capacity` bytes, evicting the oldest bytes
+/// once full, so the oldest retained line may be cut short at its start. [push](RingBuffer::push) delimits entries with `\n` so message boundaries survive
+/// into [snapshot](RingBuffer::snapshot)'s single undifferentiated blob.
+pub struct RingBuffer {
+    lines: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Appends `line`, delimiting it from whatever was pushed before with a trailing `\n` if
+    /// `line` doesn't already end with one, evicting the oldest bytes if the buffer would
+    /// otherwise exceed its capacity.
+    pub fn push(&mut self, line: &[u8]) {
+        self.push_bytes(line);
+        if !line.ends_with(b"\n") {
+            self.push_bytes(b"\n");
+        }
+    }
+
+    /// Appends `bytes` verbatim, evicting the oldest bytes if the buffer would otherwise exceed
+    /// its capacity.
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        if bytes.len() >= self.capacity {
+            // A single chunk larger than the whole buffer: keep only its tail.
+            self.lines.clear();
+            self.lines
+                .extend(bytes[bytes.len() - self.capacity..].iter().copied());
+            return;
+        }
+
+        while self.lines.len() + bytes.len() > self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.extend(bytes.iter().copied());
+    }
+
+    /// Copies out the buffer's current contents without clearing it.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.lines.iter().copied().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retains_most_recent_lines_within_capacity() {
+        let mut rb = RingBuffer::new(20);
+        rb.push(b"abcde");
+        rb.push(b"fghij");
+        assert_eq!(rb.snapshot(), b"abcde\nfghij\n");
+    }
+
+    #[test]
+    fn push_does_not_duplicate_an_existing_trailing_newline() {
+        let mut rb = RingBuffer::new(20);
+        rb.push(b"abcde\n");
+        rb.push(b"fghij\n");
+        assert_eq!(rb.snapshot(), b"abcde\nfghij\n");
+    }
+
+    #[test]
+    fn evicts_oldest_bytes_once_over_capacity() {
+        let mut rb = RingBuffer::new(12);
+        rb.push(b"abcde");
+        rb.push(b"fghij");
+        rb.push(b"klmno");
+        assert_eq!(rb.snapshot(), b"fghij\nklmno\n");
+    }
+
+    #[test]
+    fn clear_empties_the_buffer() {
+        let mut rb = RingBuffer::new(10);
+        rb.push(b"abcde");
+        rb.clear();
+        assert!(rb.snapshot().is_empty());
+    }
+
+    #[test]
+    fn oversized_line_keeps_only_its_tail() {
+        let mut rb = RingBuffer::new(4);
+        rb.push(b"abcdefgh");
+        assert_eq!(rb.snapshot(), b"fgh\n");
+    }
+}
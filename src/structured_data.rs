@@ -0,0 +1,112 @@
+//! Builds an RFC 5424 STRUCTURED-DATA element from the key-values attached to a [log::Record].
+use log::kv::{Error, Key, Source, Value, VisitSource};
+
+/// Identifies the SD-ID under which a record's key-values are reported, per
+/// [RFC 5424 section 7](https://datatracker.ietf.org/doc/html/rfc5424#section-7): `name@enterprise_id`.
+#[derive(Clone)]
+pub struct StructuredDataConfig {
+    /// The SD-NAME portion of the SD-ID, e.g. `"flexi"`.
+    pub sd_id: String,
+    /// The IANA private enterprise number, e.g. `32473`.
+    pub enterprise_id: u32,
+}
+
+impl StructuredDataConfig {
+    pub fn new(sd_id: impl Into<String>, enterprise_id: u32) -> Self {
+        Self {
+            sd_id: sd_id.into(),
+            enterprise_id,
+        }
+    }
+
+    /// Visits `source`'s key-values and renders a single `[id key="value" ...]` SD-ELEMENT.
+    /// Returns `None` if `source` carries no key-values at all.
+    pub fn render(&self, source: &dyn Source) -> Option<String> {
+        let mut collector = SdElementCollector {
+            params: String::new(),
+        };
+        // `log::kv::VisitSource::visit_pair` never errors for our collector, so the only
+        // failure mode here is a caller-supplied `Source` that errors internally.
+        let _ = source.visit(&mut collector);
+
+        if collector.params.is_empty() {
+            return None;
+        }
+
+        Some(format!("[{}@{}{}]", self.sd_id, self.enterprise_id, collector.params))
+    }
+}
+
+struct SdElementCollector {
+    params: String,
+}
+
+impl<'kvs> VisitSource<'kvs> for SdElementCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let key = key.as_str();
+        if !is_valid_sd_name(key) {
+            return Ok(());
+        }
+
+        self.params.push(' ');
+        self.params.push_str(key);
+        self.params.push_str("=\"");
+        escape_param_value(&value.to_string(), &mut self.params);
+        self.params.push('"');
+
+        Ok(())
+    }
+}
+
+/// An SD-NAME is 1-32 printable ASCII characters, excluding `=`, space, `]`, and `"`.
+fn is_valid_sd_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !matches!(c, '=' | ' ' | ']' | '"'))
+}
+
+/// Escapes the three reserved PARAM-VALUE characters per RFC 5424 section 6.3.3: `"`, `\`, `]`.
+fn escape_param_value(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            ']' => out.push_str("\\]"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_reserved_characters() {
+        let mut out = String::new();
+        escape_param_value(r#"back\slash "quote" bracket]"#, &mut out);
+        assert_eq!(out, r#"back\\slash \"quote\" bracket\]"#);
+    }
+
+    #[test]
+    fn rejects_invalid_sd_names() {
+        assert!(!is_valid_sd_name("has space"));
+        assert!(!is_valid_sd_name("has=equals"));
+        assert!(!is_valid_sd_name("has]bracket"));
+        assert!(!is_valid_sd_name("has\"quote"));
+        assert!(!is_valid_sd_name(""));
+        assert!(!is_valid_sd_name(&"x".repeat(33)));
+        assert!(is_valid_sd_name("mycrate.request_id"));
+    }
+
+    #[test]
+    fn renders_sd_element() {
+        let config = StructuredDataConfig::new("flexi", 32473);
+        let kvs = [("request_id", "abc123")];
+        let source: &dyn Source = &kvs[..];
+        let rendered = config.render(source).expect("non-empty key-values");
+        assert_eq!(rendered, r#"[flexi@32473 request_id="abc123"]"#);
+    }
+}